@@ -0,0 +1,137 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// A four-stage attack/decay/sustain/release envelope generator.
+///
+/// Call [`Envelope::trigger`] on a NoteOn and [`Envelope::release`] on a NoteOff, then pull one
+/// value per sample from [`Envelope::next`].
+pub struct Envelope {
+    stage: Stage,
+    value: f32,
+    attack_increment: f32,
+    decay_increment: f32,
+    sustain_level: f32,
+    release_increment: f32,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Self {
+            stage: Stage::Idle,
+            value: 0.0,
+            attack_increment: 0.0,
+            decay_increment: 0.0,
+            sustain_level: 0.0,
+            release_increment: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.stage = Stage::Idle;
+        self.value = 0.0;
+    }
+
+    /// Whether the envelope has finished releasing (or was never triggered).
+    pub fn is_idle(&self) -> bool {
+        self.stage == Stage::Idle
+    }
+
+    /// Starts a new attack/decay/sustain cycle from silence, with the given stage times (in
+    /// seconds) and sustain level resolved into per-sample increments.
+    pub fn trigger(&mut self, sample_rate: f32, attack: f32, decay: f32, sustain: f32) {
+        self.stage = Stage::Attack;
+        self.value = 0.0;
+        self.attack_increment = 1.0 / (attack.max(1e-3) * sample_rate);
+        self.decay_increment = (1.0 - sustain) / (decay.max(1e-3) * sample_rate);
+        self.sustain_level = sustain;
+    }
+
+    /// Moves into the release stage, ramping the current value down to zero over `release`
+    /// seconds.
+    pub fn release(&mut self, sample_rate: f32, release: f32) {
+        self.stage = Stage::Release;
+        self.release_increment = self.value / (release.max(1e-3) * sample_rate);
+    }
+
+    /// Advances the envelope by one sample and returns its current value.
+    pub fn next(&mut self) -> f32 {
+        match self.stage {
+            Stage::Idle => 0.0,
+            Stage::Attack => {
+                self.value += self.attack_increment;
+                if self.value >= 1.0 {
+                    self.value = 1.0;
+                    self.stage = Stage::Decay;
+                }
+                self.value
+            }
+            Stage::Decay => {
+                self.value -= self.decay_increment;
+                if self.value <= self.sustain_level {
+                    self.value = self.sustain_level;
+                    self.stage = Stage::Sustain;
+                }
+                self.value
+            }
+            Stage::Sustain => self.value,
+            Stage::Release => {
+                self.value -= self.release_increment;
+                if self.value <= 0.0 {
+                    self.value = 0.0;
+                    self.stage = Stage::Idle;
+                }
+                self.value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_before_trigger() {
+        let envelope = Envelope::new();
+        assert!(envelope.is_idle());
+    }
+
+    #[test]
+    fn runs_through_every_stage_in_order() {
+        let mut envelope = Envelope::new();
+        envelope.trigger(10.0, 0.1, 0.1, 0.5);
+        assert_eq!(envelope.stage, Stage::Attack);
+
+        assert_eq!(envelope.next(), 1.0);
+        assert_eq!(envelope.stage, Stage::Decay);
+
+        assert_eq!(envelope.next(), 0.5);
+        assert_eq!(envelope.stage, Stage::Sustain);
+
+        // Sustain holds its level until released.
+        assert_eq!(envelope.next(), 0.5);
+        assert_eq!(envelope.stage, Stage::Sustain);
+
+        envelope.release(10.0, 0.1);
+        assert_eq!(envelope.stage, Stage::Release);
+        assert!(!envelope.is_idle());
+
+        assert_eq!(envelope.next(), 0.0);
+        assert!(envelope.is_idle());
+    }
+
+    #[test]
+    fn reset_forces_idle() {
+        let mut envelope = Envelope::new();
+        envelope.trigger(10.0, 0.1, 0.1, 0.5);
+        envelope.reset();
+        assert!(envelope.is_idle());
+        assert_eq!(envelope.next(), 0.0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::f32::consts::PI;
+
+/// A topology-preserving-transform (TPT) state-variable filter, producing a resonant low-pass
+/// output. See Vadim Zavalishin's "The Art of VA Filter Design" for the derivation.
+pub struct SvfFilter {
+    sample_rate: f32,
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl SvfFilter {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+
+    /// Processes one sample, returning the low-pass output for the given cutoff frequency (in
+    /// Hz, clamped to a stable range) and resonance `q`.
+    pub fn process(&mut self, input: f32, cutoff: f32, q: f32) -> f32 {
+        let cutoff = cutoff.clamp(20.0, self.sample_rate * 0.49);
+        let g = (PI * cutoff / self.sample_rate).tan();
+        let k = 1.0 / q.max(0.01);
+
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        v2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(filter: &mut SvfFilter, cutoff: f32, q: f32, samples: usize) -> f32 {
+        let mut output = 0.0;
+        for i in 0..samples {
+            let input = if i % 2 == 0 { 1.0 } else { -1.0 };
+            output = filter.process(input, cutoff, q);
+        }
+        output
+    }
+
+    #[test]
+    fn stays_finite_with_cutoff_below_the_clamp_range() {
+        let mut filter = SvfFilter::new(44_100.0);
+        let output = run(&mut filter, 0.0, 0.7, 64);
+        assert!(output.is_finite());
+    }
+
+    #[test]
+    fn stays_finite_with_cutoff_above_the_clamp_range() {
+        let mut filter = SvfFilter::new(44_100.0);
+        let output = run(&mut filter, 100_000.0, 0.7, 64);
+        assert!(output.is_finite());
+    }
+
+    #[test]
+    fn stays_finite_at_near_self_oscillating_resonance() {
+        let mut filter = SvfFilter::new(44_100.0);
+        let output = run(&mut filter, 1_000.0, 20.0, 64);
+        assert!(output.is_finite());
+    }
+
+    #[test]
+    fn stays_finite_with_a_degenerate_q() {
+        let mut filter = SvfFilter::new(44_100.0);
+        let output = run(&mut filter, 1_000.0, 0.0, 64);
+        assert!(output.is_finite());
+    }
+
+    #[test]
+    fn reset_clears_carried_over_state() {
+        let mut filter = SvfFilter::new(44_100.0);
+        run(&mut filter, 1_000.0, 20.0, 64);
+        filter.reset();
+        assert_eq!(filter.ic1eq, 0.0);
+        assert_eq!(filter.ic2eq, 0.0);
+    }
+}
@@ -1,12 +1,19 @@
+mod envelope;
+mod filter;
+mod midi_cc;
 mod oscillators;
 pub mod parametric_equation;
+mod voice;
+mod waveguide;
 
 use crate::oscillators::ParametricOscillator;
+use crate::voice::VoiceManager;
 
 use nih_plug::prelude::*;
 use std::sync::Arc;
 use nih_plug_egui::{create_egui_editor, egui, widgets, EguiState};
-use crate::parametric_equation::{EquationA, ParametricEquation};
+use crate::parametric_equation::{CurveFamily, ParametricEquation};
+use crate::waveguide::SynthEngine;
 
 /// A test tone generator that can either generate a sine wave based on the plugin's parameters or
 /// based on the current MIDI input.
@@ -15,19 +22,12 @@ pub struct Paramic {
 
     sample_rate: f32,
 
-    /// The underlying oscillator
+    /// The underlying oscillator, used when the plugin is driven by the frequency parameter
+    /// instead of MIDI.
     oscillator: oscillators::ParametricOscillatorA,
 
-    /// The MIDI note ID of the active note, if triggered by MIDI.
-    midi_note_id: u8,
-    /// The frequency if the active note, if triggered by MIDI.
-    midi_note_freq: f32,
-    /// A simple attack and release envelope to avoid clicks. Controlled through velocity and
-    /// aftertouch.
-    ///
-    /// Smoothing is built into the parameters, but you can also use them manually if you need to
-    /// smooth soemthing that isn't a parameter.
-    midi_note_gain: Smoother<f32>,
+    /// The pool of polyphonic voices driven by incoming MIDI note events.
+    voice_manager: VoiceManager,
 }
 
 #[derive(Params)]
@@ -63,29 +63,78 @@ struct ParamicParams {
 
     #[id = "k"]
     pub k: IntParam,
+
+    #[id = "attack"]
+    pub attack: FloatParam,
+
+    #[id = "decay"]
+    pub decay: FloatParam,
+
+    #[id = "sustain"]
+    pub sustain: FloatParam,
+
+    #[id = "release"]
+    pub release: FloatParam,
+
+    #[id = "cutoff"]
+    pub cutoff: FloatParam,
+
+    #[id = "resonance"]
+    pub resonance: FloatParam,
+
+    #[id = "fenvamt"]
+    pub filter_env_amount: FloatParam,
+
+    #[id = "fattack"]
+    pub filter_attack: FloatParam,
+
+    #[id = "fdecay"]
+    pub filter_decay: FloatParam,
+
+    #[id = "fsustain"]
+    pub filter_sustain: FloatParam,
+
+    #[id = "frelease"]
+    pub filter_release: FloatParam,
+
+    #[id = "pdamt"]
+    pub pd_amount: FloatParam,
+
+    #[id = "family"]
+    pub family: EnumParam<CurveFamily>,
+
+    #[id = "engine"]
+    pub engine: EnumParam<SynthEngine>,
+
+    #[id = "damping"]
+    pub damping: FloatParam,
+
+    #[id = "brightness"]
+    pub excitation_brightness: FloatParam,
 }
 
 impl Default for Paramic {
 
     fn default() -> Self {
+        let default_params = ParamicParams::default();
+        let family = default_params.family.value();
+        let (a, b, c, d, j, k) = (
+            default_params.a.value(),
+            default_params.b.value(),
+            default_params.c.value(),
+            default_params.d.value(),
+            default_params.j.value(),
+            default_params.k.value(),
+        );
+
         Self {
             params: Arc::new(ParamicParams::default()),
 
             sample_rate: 1.0,
 
-            oscillator: oscillators::ParametricOscillatorA::new(
-                1.0, EquationA {
-                    a: ParamicParams::default().a.value(),
-                    b: ParamicParams::default().b.value(),
-                    c: ParamicParams::default().c.value(),
-                    d: ParamicParams::default().d.value(),
-                    j: ParamicParams::default().j.value(),
-                    k: ParamicParams::default().k.value(),
-                }),
-
-            midi_note_id: 0,
-            midi_note_freq: 1.0,
-            midi_note_gain: Smoother::new(SmoothingStyle::Linear(5.0)),
+            oscillator: oscillators::ParametricOscillatorA::new(1.0, family.build(a, b, c, d, j, k)),
+
+            voice_manager: VoiceManager::new(1.0, family, a, b, c, d, j, k),
         }
     }
 }
@@ -170,6 +219,144 @@ impl Default for ParamicParams {
                     max: 100,
                 },
             ),
+
+            attack: FloatParam::new(
+                "Attack",
+                0.005,
+                FloatRange::Skewed {
+                    min: 0.001,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_unit(" s"),
+
+            decay: FloatParam::new(
+                "Decay",
+                0.1,
+                FloatRange::Skewed {
+                    min: 0.001,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_unit(" s"),
+
+            sustain: FloatParam::new(
+                "Sustain",
+                0.8,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ),
+
+            release: FloatParam::new(
+                "Release",
+                0.1,
+                FloatRange::Skewed {
+                    min: 0.001,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_unit(" s"),
+
+            cutoff: FloatParam::new(
+                "Cutoff",
+                20_000.0,
+                FloatRange::Skewed {
+                    min: 20.0,
+                    max: 20_000.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+                .with_string_to_value(formatters::s2v_f32_hz_then_khz()),
+
+            resonance: FloatParam::new(
+                "Resonance",
+                0.707,
+                FloatRange::Linear {
+                    min: 0.1,
+                    max: 20.0,
+                },
+            ),
+
+            filter_env_amount: FloatParam::new(
+                "Filter Env Amount",
+                0.0,
+                FloatRange::Linear {
+                    min: -10_000.0,
+                    max: 10_000.0,
+                },
+            ).with_unit(" Hz"),
+
+            filter_attack: FloatParam::new(
+                "Filter Attack",
+                0.005,
+                FloatRange::Skewed {
+                    min: 0.001,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_unit(" s"),
+
+            filter_decay: FloatParam::new(
+                "Filter Decay",
+                0.1,
+                FloatRange::Skewed {
+                    min: 0.001,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_unit(" s"),
+
+            filter_sustain: FloatParam::new(
+                "Filter Sustain",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ),
+
+            filter_release: FloatParam::new(
+                "Filter Release",
+                0.1,
+                FloatRange::Skewed {
+                    min: 0.001,
+                    max: 5.0,
+                    factor: FloatRange::skew_factor(-2.0),
+                },
+            ).with_unit(" s"),
+
+            pd_amount: FloatParam::new(
+                "PD Amount",
+                0.5,
+                FloatRange::Linear {
+                    min: 0.01,
+                    max: 0.99,
+                },
+            ),
+
+            family: EnumParam::new("Curve Family", CurveFamily::EquationA),
+
+            engine: EnumParam::new("Engine", SynthEngine::Parametric),
+
+            damping: FloatParam::new(
+                "Damping",
+                0.995,
+                FloatRange::Linear {
+                    min: 0.8,
+                    max: 0.9999,
+                },
+            ),
+
+            excitation_brightness: FloatParam::new(
+                "Excitation Brightness",
+                0.5,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1.0,
+                },
+            ),
         }
     }
 }
@@ -236,17 +423,43 @@ impl Plugin for Paramic {
                     ui.add(widgets::ParamSlider::for_param(&params.j, setter));
                     ui.add(widgets::ParamSlider::for_param(&params.k, setter));
 
+                    ui.label("PD Amount");
+                    ui.add(widgets::ParamSlider::for_param(&params.pd_amount, setter));
+
                     ui.label("Use MIDI");
                     ui.add(widgets::ParamSlider::for_param(&params.use_midi, setter));
 
-                    let equation = EquationA {
-                        a: params.a.value(),
-                        b: params.b.value(),
-                        c: params.c.value(),
-                        d: params.d.value(),
-                        j: params.j.value(),
-                        k: params.k.value(),
-                    };
+                    ui.label("Amplitude Envelope");
+                    ui.add(widgets::ParamSlider::for_param(&params.attack, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.decay, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.sustain, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.release, setter));
+
+                    ui.label("Filter");
+                    ui.add(widgets::ParamSlider::for_param(&params.cutoff, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.resonance, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.filter_env_amount, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.filter_attack, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.filter_decay, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.filter_sustain, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.filter_release, setter));
+
+                    ui.label("Curve Family");
+                    ui.add(widgets::ParamSlider::for_param(&params.family, setter));
+
+                    ui.label("Engine");
+                    ui.add(widgets::ParamSlider::for_param(&params.engine, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.damping, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.excitation_brightness, setter));
+
+                    let equation = params.family.value().build(
+                        params.a.value(),
+                        params.b.value(),
+                        params.c.value(),
+                        params.d.value(),
+                        params.j.value(),
+                        params.k.value(),
+                    );
 
                     let period = equation.get_period();
                     
@@ -296,14 +509,13 @@ impl Plugin for Paramic {
     ) -> bool {
         self.sample_rate = buffer_config.sample_rate;
         self.oscillator.set_sample_rate(buffer_config.sample_rate);
+        self.voice_manager.set_sample_rate(buffer_config.sample_rate);
 
         true
     }
 
     fn reset(&mut self) {
-        self.midi_note_id = 0;
-        self.midi_note_freq = 1.0;
-        self.midi_note_gain.reset(0.0);
+        self.voice_manager.reset();
     }
 
     fn process(
@@ -314,14 +526,20 @@ impl Plugin for Paramic {
     ) -> ProcessStatus {
         let mut next_event = context.next_event();
 
-        self.oscillator.set_equation(EquationA {
-            a: self.params.a.value(),
-            b: self.params.b.value(),
-            c: self.params.c.value(),
-            d: self.params.d.value(),
-            j: self.params.j.value(),
-            k: self.params.k.value(),
-        });
+        let family = self.params.family.value();
+        let (a, b, c, d, j, k) = (
+            self.params.a.value(),
+            self.params.b.value(),
+            self.params.c.value(),
+            self.params.d.value(),
+            self.params.j.value(),
+            self.params.k.value(),
+        );
+        self.oscillator.set_equation(family.build(a, b, c, d, j, k));
+        self.voice_manager.set_equation(family, a, b, c, d, j, k);
+
+        self.oscillator.set_pd_amount(self.params.pd_amount.value());
+        self.voice_manager.set_pd_amount(self.params.pd_amount.value());
 
         for (sample_id, channel_samples) in buffer.iter_samples().enumerate() {
             // Smoothing is optionally built into the parameters themselves
@@ -337,26 +555,29 @@ impl Plugin for Paramic {
 
                     match event {
                         NoteEvent::NoteOn { note, velocity, .. } => {
-                            self.midi_note_id = note;
-                            self.midi_note_freq = util::midi_note_to_freq(note);
-                            self.midi_note_gain.set_target(self.sample_rate, velocity);
+                            self.voice_manager.note_on(
+                                note,
+                                velocity,
+                                self.sample_rate,
+                                &self.params,
+                            );
+                        }
+                        NoteEvent::NoteOff { note, .. } => {
+                            self.voice_manager.note_off(note, self.sample_rate, &self.params);
+                        }
+                        NoteEvent::PolyPressure { note, pressure, .. } => {
+                            self.voice_manager.set_pressure(note, pressure);
                         }
-                        NoteEvent::NoteOff { note, .. } if note == self.midi_note_id => {
-                            self.midi_note_gain.set_target(self.sample_rate, 0.0);
+                        NoteEvent::MidiCC { cc, value, .. } => {
+                            midi_cc::handle(&self.params, cc, value);
                         }
-                        NoteEvent::PolyPressure { note, pressure, .. }
-                        if note == self.midi_note_id =>
-                            {
-                                self.midi_note_gain.set_target(self.sample_rate, pressure);
-                            }
                         _ => (),
                     }
 
                     next_event = context.next_event();
                 }
 
-                // This gain envelope prevents clicks with new notes and with released notes
-                self.calculate_sample(self.midi_note_freq) * self.midi_note_gain.next()
+                self.voice_manager.sample(&self.params)
             } else {
                 let frequency = self.params.frequency.smoothed.next();
                 self.calculate_sample(frequency)
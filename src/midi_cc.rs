@@ -0,0 +1,74 @@
+use crate::ParamicParams;
+
+/// Maps an incoming MIDI CC number to one of the plugin's parameters, linearly rescaling the
+/// normalized `0.0..=1.0` CC value into the parameter's plain value range.
+///
+/// This bypasses the GUI's begin/end automation gesture since the change originates from a MIDI
+/// controller rather than a user dragging a knob.
+pub struct CcMapping {
+    pub cc: u8,
+    pub min: f32,
+    pub max: f32,
+    pub apply: fn(&ParamicParams, f32),
+}
+
+/// The default CC -> parameter scheme: volume on CC7, filter cutoff/resonance on CC74/71,
+/// amplitude envelope times on CC73/72, and the parametric coefficients on CC20-25.
+pub const CC_MAPPINGS: &[CcMapping] = &[
+    CcMapping { cc: 7, min: -30.0, max: 0.0, apply: |p, v| p.gain.set_plain_value(v) },
+    CcMapping { cc: 74, min: 20.0, max: 20_000.0, apply: |p, v| p.cutoff.set_plain_value(v) },
+    CcMapping { cc: 71, min: 0.1, max: 20.0, apply: |p, v| p.resonance.set_plain_value(v) },
+    CcMapping { cc: 73, min: 0.001, max: 5.0, apply: |p, v| p.attack.set_plain_value(v) },
+    CcMapping { cc: 72, min: 0.001, max: 5.0, apply: |p, v| p.release.set_plain_value(v) },
+    CcMapping { cc: 20, min: 1.0, max: 100.0, apply: |p, v| p.a.set_plain_value(v as i32) },
+    CcMapping { cc: 21, min: 1.0, max: 100.0, apply: |p, v| p.b.set_plain_value(v as i32) },
+    CcMapping { cc: 22, min: 1.0, max: 100.0, apply: |p, v| p.c.set_plain_value(v as i32) },
+    CcMapping { cc: 23, min: 1.0, max: 100.0, apply: |p, v| p.d.set_plain_value(v as i32) },
+    CcMapping { cc: 24, min: 1.0, max: 100.0, apply: |p, v| p.j.set_plain_value(v as i32) },
+    CcMapping { cc: 25, min: 1.0, max: 100.0, apply: |p, v| p.k.set_plain_value(v as i32) },
+];
+
+/// Applies a normalized `0.0..=1.0` CC value to every mapping registered for `cc`.
+pub fn handle(params: &ParamicParams, cc: u8, value: f32) {
+    for mapping in CC_MAPPINGS {
+        if mapping.cc == cc {
+            let scaled = mapping.min + value * (mapping.max - mapping.min);
+            (mapping.apply)(params, scaled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmapped_cc_is_a_no_op() {
+        let params = ParamicParams::default();
+        let before = params.gain.value();
+
+        handle(&params, 255, 1.0);
+
+        assert_eq!(params.gain.value(), before);
+    }
+
+    #[test]
+    fn mapped_cc_scales_into_the_parameter_range() {
+        let params = ParamicParams::default();
+
+        handle(&params, 7, 0.5);
+
+        assert_eq!(params.gain.value(), -15.0);
+    }
+
+    #[test]
+    fn mapped_cc_at_the_extremes_hits_the_mapping_bounds() {
+        let params = ParamicParams::default();
+
+        handle(&params, 74, 0.0);
+        assert_eq!(params.cutoff.value(), 20.0);
+
+        handle(&params, 74, 1.0);
+        assert_eq!(params.cutoff.value(), 20_000.0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::f32::consts;
-use super::parametric_equation::{EquationA, ParametricEquation};
+use super::parametric_equation::{Equation, ParametricEquation};
 
 pub trait Oscillator {
     fn set_sample_rate(&mut self, sample_rate: f32);
@@ -83,28 +83,47 @@ impl Oscillator for SquareOscillator {
 pub trait ParametricOscillator {
     fn set_sample_rate(&mut self, sample_rate: f32);
     fn set_frequency(&mut self, frequency: f32);
-    fn set_equation(&mut self, equation: EquationA);
+    fn set_equation(&mut self, equation: Equation);
     fn sample(&mut self) -> f32;
 }
 
+/// Warps a normalized phase `p` in `[0, 1)` around the breakpoint `d` in `(0, 1)`, mapping `[0, d)`
+/// onto `[0, 1)` and `[d, 1)` onto `[1, 2)`. For `d < 0.5` this speeds the curve's traversal
+/// through `[0, d)` and slows it through `[d, 1)`; for `d > 0.5` the reverse.
+fn warp_phase(p: f64, d: f64) -> f64 {
+    let d = d.clamp(1e-4, 1.0 - 1e-4);
+    if p < d {
+        p / d
+    } else {
+        1.0 + (p - d) / (1.0 - d)
+    }
+}
+
 pub struct ParametricOscillatorA {
     sample_rate: f32,
     phase: f32,
     frequency: f32,
-    equation: EquationA,
+    equation: Equation,
     period : f64,
+    /// The phase-distortion breakpoint `d`, in `(0, 1)`. `0.5` leaves the phase untouched.
+    pd_amount: f32,
 }
 
 impl ParametricOscillatorA {
-    pub fn new(sample_rate: f32, equation: EquationA) -> Self {
+    pub fn new(sample_rate: f32, equation: Equation) -> Self {
         Self {
             sample_rate,
             phase: 0.0,
             frequency: 440.0,
             period: equation.get_period(),
             equation,
+            pd_amount: 0.5,
         }
     }
+
+    pub fn set_pd_amount(&mut self, pd_amount: f32) {
+        self.pd_amount = pd_amount;
+    }
 }
 
 impl ParametricOscillator for ParametricOscillatorA {
@@ -117,7 +136,8 @@ impl ParametricOscillator for ParametricOscillatorA {
         self.frequency = frequency;
     }
 
-    fn set_equation(&mut self, equation: EquationA) {
+    fn set_equation(&mut self, equation: Equation) {
+        self.period = equation.get_period();
         self.equation = equation;
     }
 
@@ -126,7 +146,38 @@ impl ParametricOscillator for ParametricOscillatorA {
         if self.phase > self.period as f32 {
             self.phase -= self.period as f32;
         }
-        let (x, y) = self.equation.get_position(self.phase as f64);
+
+        let normalized_phase = (self.phase as f64 / self.period).clamp(0.0, 1.0);
+        let warped = warp_phase(normalized_phase, self.pd_amount as f64);
+        let t = warped * 0.5 * self.period;
+
+        let (x, y) = self.equation.get_position(t);
         ((x.powi(2) + y.powi(2)).sqrt() - 1.0) as f32
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warp_phase_is_continuous_at_the_breakpoint() {
+        let d = 0.3;
+        assert_eq!(warp_phase(d, d), 1.0);
+        assert!((warp_phase(d - 1e-9, d) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn warp_phase_covers_the_full_output_range() {
+        let d = 0.5;
+        assert_eq!(warp_phase(0.0, d), 0.0);
+        assert!((warp_phase(1.0, d) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn warp_phase_clamps_extreme_breakpoints() {
+        // `d` is clamped away from 0 and 1 so neither branch divides by zero.
+        assert!(warp_phase(0.5, 0.0).is_finite());
+        assert!(warp_phase(0.5, 1.0).is_finite());
+    }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
+use nih_plug::prelude::Enum;
 use num::Integer;
+use std::f64::consts::{PI, TAU};
 
 pub trait ParametricEquation {
     fn get_position(&self, t: f64) -> (f64, f64);
@@ -28,7 +30,164 @@ impl ParametricEquation for EquationA {
         let x_gcd = self.a.gcd(&self.b);
         let y_gcd = self.c.gcd(&self.d);
         let gcd = x_gcd.gcd(&y_gcd);
-        
-        std::f64::consts::TAU / gcd as f64
+
+        TAU / gcd as f64
+    }
+}
+
+/// The classic Lissajous figure: `x = sin(a*t + delta)`, `y = sin(b*t)`.
+#[derive(Debug, Clone)]
+pub struct LissajousEquation {
+    pub a: i32,
+    pub b: i32,
+    pub delta: f64,
+}
+
+impl ParametricEquation for LissajousEquation {
+    fn get_position(&self, t: f64) -> (f64, f64) {
+        let x = (t * self.a as f64 + self.delta).sin();
+        let y = (t * self.b as f64).sin();
+        (x, y)
+    }
+
+    fn get_period(&self) -> f64 {
+        TAU / self.a.gcd(&self.b).max(1) as f64
+    }
+}
+
+/// A rose curve: `x = cos(k*t)*cos(t)`, `y = cos(k*t)*sin(t)`.
+#[derive(Debug, Clone)]
+pub struct RoseEquation {
+    pub k: i32,
+}
+
+impl ParametricEquation for RoseEquation {
+    fn get_position(&self, t: f64) -> (f64, f64) {
+        let petal = (self.k as f64 * t).cos();
+        (petal * t.cos(), petal * t.sin())
+    }
+
+    fn get_period(&self) -> f64 {
+        if self.k % 2 == 0 {
+            TAU
+        } else {
+            PI
+        }
+    }
+}
+
+/// An epicycloid: a circle of radius `b` rolling around the outside of a circle of radius `a`.
+#[derive(Debug, Clone)]
+pub struct EpicycloidEquation {
+    pub a: i32,
+    pub b: i32,
+}
+
+impl ParametricEquation for EpicycloidEquation {
+    fn get_position(&self, t: f64) -> (f64, f64) {
+        let a = self.a as f64;
+        let b = self.b as f64;
+        let ratio = (a + b) / b;
+        let x = (a + b) * t.cos() - b * (ratio * t).cos();
+        let y = (a + b) * t.sin() - b * (ratio * t).sin();
+        (x, y)
+    }
+
+    fn get_period(&self) -> f64 {
+        TAU * self.b as f64 / self.a.gcd(&self.b).max(1) as f64
+    }
+}
+
+/// The available curve families a [`crate::oscillators::ParametricOscillatorA`] can be built
+/// from. The shared a/b/c/d/j/k coefficients are reinterpreted per family.
+#[derive(Enum, Debug, PartialEq, Clone, Copy)]
+pub enum CurveFamily {
+    EquationA,
+    Lissajous,
+    Rose,
+    Epicycloid,
+}
+
+impl CurveFamily {
+    /// Builds the equation for this family from the shared a/b/c/d/j/k coefficients.
+    pub fn build(self, a: i32, b: i32, c: i32, d: i32, j: i32, k: i32) -> Equation {
+        match self {
+            CurveFamily::EquationA => Equation::EquationA(EquationA { a, b, c, d, j, k }),
+            CurveFamily::Lissajous => Equation::Lissajous(LissajousEquation { a, b, delta: c as f64 }),
+            CurveFamily::Rose => Equation::Rose(RoseEquation { k }),
+            CurveFamily::Epicycloid => Equation::Epicycloid(EpicycloidEquation { a, b }),
+        }
+    }
+}
+
+/// A [`ParametricEquation`] picked at runtime by [`CurveFamily`], dispatched through a plain enum
+/// match rather than a `Box<dyn ParametricEquation>` so that rebuilding it (once per audio buffer,
+/// as the a/b/c/d/j/k parameters are re-read) never touches the heap.
+#[derive(Debug, Clone)]
+pub enum Equation {
+    EquationA(EquationA),
+    Lissajous(LissajousEquation),
+    Rose(RoseEquation),
+    Epicycloid(EpicycloidEquation),
+}
+
+impl ParametricEquation for Equation {
+    fn get_position(&self, t: f64) -> (f64, f64) {
+        match self {
+            Equation::EquationA(equation) => equation.get_position(t),
+            Equation::Lissajous(equation) => equation.get_position(t),
+            Equation::Rose(equation) => equation.get_position(t),
+            Equation::Epicycloid(equation) => equation.get_position(t),
+        }
+    }
+
+    fn get_period(&self) -> f64 {
+        match self {
+            Equation::EquationA(equation) => equation.get_period(),
+            Equation::Lissajous(equation) => equation.get_period(),
+            Equation::Rose(equation) => equation.get_period(),
+            Equation::Epicycloid(equation) => equation.get_period(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lissajous_period_and_position_are_finite() {
+        let equation = LissajousEquation { a: 3, b: 2, delta: PI / 2.0 };
+        let period = equation.get_period();
+        assert!(period.is_finite() && period > 0.0);
+
+        let (x, y) = equation.get_position(period * 0.25);
+        assert!(x.is_finite() && y.is_finite());
+    }
+
+    #[test]
+    fn rose_period_depends_on_k_parity() {
+        assert_eq!(RoseEquation { k: 4 }.get_period(), TAU);
+        assert_eq!(RoseEquation { k: 3 }.get_period(), PI);
+
+        let (x, y) = RoseEquation { k: 5 }.get_position(0.7);
+        assert!(x.is_finite() && y.is_finite());
+    }
+
+    #[test]
+    fn epicycloid_period_and_position_are_finite() {
+        let equation = EpicycloidEquation { a: 5, b: 3 };
+        let period = equation.get_period();
+        assert!(period.is_finite() && period > 0.0);
+
+        let (x, y) = equation.get_position(period * 0.5);
+        assert!(x.is_finite() && y.is_finite());
+    }
+
+    #[test]
+    fn curve_family_build_dispatches_to_the_matching_equation() {
+        let equation = CurveFamily::Lissajous.build(3, 2, 0, 0, 0, 0);
+        assert!(matches!(equation, Equation::Lissajous(_)));
+        assert!(equation.get_period().is_finite());
     }
 }
\ No newline at end of file
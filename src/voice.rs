@@ -0,0 +1,344 @@
+use nih_plug::prelude::*;
+
+use crate::envelope::Envelope;
+use crate::filter::SvfFilter;
+use crate::oscillators::{ParametricOscillator, ParametricOscillatorA};
+use crate::parametric_equation::CurveFamily;
+use crate::waveguide::{SynthEngine, WaveguideVoice};
+use crate::ParamicParams;
+
+/// The number of voices kept in the pool. NoteOns beyond this are handled by stealing.
+pub const NUM_VOICES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VoiceState {
+    /// Not playing anything and free to be allocated by the next NoteOn.
+    Idle,
+    /// Held down by a NoteOn, no matching NoteOff has arrived yet.
+    Active,
+    /// Released, fading out its gain envelope before becoming idle again.
+    Releasing,
+}
+
+/// A single voice in the pool: its own oscillator, filter, envelopes and the MIDI note it is
+/// currently playing (if any).
+struct Voice {
+    state: VoiceState,
+    /// The MIDI note ID this voice is currently playing.
+    note: u8,
+    /// Allocation order, used to find the oldest voice when the pool is full and a note needs to
+    /// steal one.
+    age: u64,
+    /// The NoteOn velocity, kept around so aftertouch can blend with it without losing the
+    /// original accent.
+    velocity: f32,
+    oscillator: ParametricOscillatorA,
+    waveguide: WaveguideVoice,
+    envelope: Envelope,
+    filter: SvfFilter,
+    filter_envelope: Envelope,
+}
+
+impl Voice {
+    fn new(
+        sample_rate: f32,
+        family: CurveFamily,
+        a: i32,
+        b: i32,
+        c: i32,
+        d: i32,
+        j: i32,
+        k: i32,
+        seed: u32,
+    ) -> Self {
+        Self {
+            state: VoiceState::Idle,
+            note: 0,
+            age: 0,
+            velocity: 0.0,
+            oscillator: ParametricOscillatorA::new(sample_rate, family.build(a, b, c, d, j, k)),
+            waveguide: WaveguideVoice::new(sample_rate, seed),
+            envelope: Envelope::new(),
+            filter: SvfFilter::new(sample_rate),
+            filter_envelope: Envelope::new(),
+        }
+    }
+
+    fn sample(&mut self, params: &ParamicParams) -> f32 {
+        if self.state == VoiceState::Idle {
+            return 0.0;
+        }
+
+        let envelope = self.envelope.next();
+        if self.state == VoiceState::Releasing && self.envelope.is_idle() {
+            self.state = VoiceState::Idle;
+        }
+
+        let filter_envelope = self.filter_envelope.next();
+        let cutoff = params.cutoff.value() + filter_envelope * params.filter_env_amount.value();
+
+        let raw = match params.engine.value() {
+            SynthEngine::Parametric => self.oscillator.sample(),
+            SynthEngine::Waveguide => self.waveguide.sample(params.damping.value()),
+        };
+
+        let dry = raw * envelope * self.velocity;
+        self.filter.process(dry, cutoff, params.resonance.value())
+    }
+}
+
+/// Allocates and drives a fixed pool of [`Voice`]s, turning MIDI note events into a polyphonic
+/// mix of parametric oscillators.
+pub struct VoiceManager {
+    voices: Vec<Voice>,
+    next_age: u64,
+}
+
+impl VoiceManager {
+    pub fn new(sample_rate: f32, family: CurveFamily, a: i32, b: i32, c: i32, d: i32, j: i32, k: i32) -> Self {
+        Self {
+            voices: (0..NUM_VOICES)
+                .map(|i| Voice::new(sample_rate, family, a, b, c, d, j, k, i as u32 * 2 + 1))
+                .collect(),
+            next_age: 1,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        for voice in &mut self.voices {
+            voice.oscillator.set_sample_rate(sample_rate);
+            voice.waveguide.set_sample_rate(sample_rate);
+            voice.filter.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// Rebuilds the shared equation once and hands every voice a clone of it.
+    /// [`crate::parametric_equation::Equation`] is a
+    /// plain enum rather than a `Box<dyn ParametricEquation>`, so this is a cheap stack copy per
+    /// voice with no heap allocation on the audio thread.
+    pub fn set_equation(&mut self, family: CurveFamily, a: i32, b: i32, c: i32, d: i32, j: i32, k: i32) {
+        let equation = family.build(a, b, c, d, j, k);
+        for voice in &mut self.voices {
+            voice.oscillator.set_equation(equation.clone());
+        }
+    }
+
+    pub fn set_pd_amount(&mut self, pd_amount: f32) {
+        for voice in &mut self.voices {
+            voice.oscillator.set_pd_amount(pd_amount);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for voice in &mut self.voices {
+            voice.state = VoiceState::Idle;
+            voice.envelope.reset();
+            voice.filter_envelope.reset();
+            voice.filter.reset();
+            voice.waveguide.reset();
+        }
+        self.next_age = 1;
+    }
+
+    /// Allocates a free voice for `note`, stealing one via [`Self::steal_voice`] if the pool is
+    /// full (a releasing voice first, otherwise the oldest active one), and
+    /// starts its amplitude and filter envelopes' attack stages.
+    pub fn note_on(&mut self, note: u8, velocity: f32, sample_rate: f32, params: &ParamicParams) {
+        let index = self
+            .voices
+            .iter()
+            .position(|voice| voice.state == VoiceState::Idle)
+            .unwrap_or_else(|| self.steal_voice());
+
+        let age = self.next_age;
+        self.next_age += 1;
+
+        let voice = &mut self.voices[index];
+        voice.state = VoiceState::Active;
+        voice.note = note;
+        voice.age = age;
+        voice.velocity = velocity;
+        // Clear out whatever the previous occupant of this voice slot left behind, so a stolen
+        // voice's filter resonance and waveguide delay line don't ring into the new note.
+        voice.filter.reset();
+        voice.waveguide.reset();
+        let frequency = util::midi_note_to_freq(note);
+        voice.oscillator.set_frequency(frequency);
+        voice
+            .waveguide
+            .trigger(frequency, params.excitation_brightness.value());
+        voice.envelope.trigger(
+            sample_rate,
+            params.attack.value(),
+            params.decay.value(),
+            params.sustain.value(),
+        );
+        voice.filter_envelope.trigger(
+            sample_rate,
+            params.filter_attack.value(),
+            params.filter_decay.value(),
+            params.filter_sustain.value(),
+        );
+    }
+
+    /// Begins releasing every voice currently holding `note`.
+    pub fn note_off(&mut self, note: u8, sample_rate: f32, params: &ParamicParams) {
+        for voice in self
+            .voices
+            .iter_mut()
+            .filter(|voice| voice.note == note && voice.state == VoiceState::Active)
+        {
+            voice.state = VoiceState::Releasing;
+            voice.envelope.release(sample_rate, params.release.value());
+            voice
+                .filter_envelope
+                .release(sample_rate, params.filter_release.value());
+        }
+    }
+
+    /// Applies aftertouch to every voice currently holding `note`, blending it with the NoteOn
+    /// velocity rather than replacing it outright.
+    pub fn set_pressure(&mut self, note: u8, pressure: f32) {
+        for voice in self
+            .voices
+            .iter_mut()
+            .filter(|voice| voice.note == note && voice.state == VoiceState::Active)
+        {
+            voice.velocity = voice.velocity.max(pressure);
+        }
+    }
+
+    /// Picks a voice to steal when the pool is full: a releasing voice if one is already fading
+    /// out, otherwise the oldest active voice.
+    fn steal_voice(&mut self) -> usize {
+        self.voices
+            .iter()
+            .enumerate()
+            .filter(|(_, voice)| voice.state == VoiceState::Releasing)
+            .min_by_key(|(_, voice)| voice.age)
+            .or_else(|| self.voices.iter().enumerate().min_by_key(|(_, voice)| voice.age))
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    /// Sums the output of every active voice for the current sample.
+    pub fn sample(&mut self, params: &ParamicParams) -> f32 {
+        self.voices.iter_mut().map(|voice| voice.sample(params)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 44_100.0;
+
+    fn manager() -> VoiceManager {
+        VoiceManager::new(SAMPLE_RATE, CurveFamily::EquationA, 1, 1, 1, 1, 1, 1)
+    }
+
+    #[test]
+    fn note_on_fills_idle_voices_in_order_before_stealing() {
+        let mut manager = manager();
+        let params = ParamicParams::default();
+
+        for note in 0..NUM_VOICES as u8 {
+            manager.note_on(note, 1.0, SAMPLE_RATE, &params);
+        }
+
+        assert!(manager.voices.iter().all(|voice| voice.state == VoiceState::Active));
+        let notes: Vec<u8> = manager.voices.iter().map(|voice| voice.note).collect();
+        assert_eq!(notes, (0..NUM_VOICES as u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn stealing_picks_the_oldest_voice_when_the_pool_is_full_of_active_voices() {
+        let mut manager = manager();
+        let params = ParamicParams::default();
+
+        for note in 0..NUM_VOICES as u8 {
+            manager.note_on(note, 1.0, SAMPLE_RATE, &params);
+        }
+
+        // Every voice is Active and none is Releasing, so the next NoteOn must steal the oldest
+        // one (note 0, allocated first).
+        manager.note_on(100, 1.0, SAMPLE_RATE, &params);
+
+        assert!(!manager
+            .voices
+            .iter()
+            .any(|voice| voice.note == 0 && voice.state == VoiceState::Active));
+        assert!(manager
+            .voices
+            .iter()
+            .any(|voice| voice.note == 100 && voice.state == VoiceState::Active));
+    }
+
+    #[test]
+    fn stealing_prefers_a_releasing_voice_over_an_active_one_regardless_of_age() {
+        let mut manager = manager();
+        let params = ParamicParams::default();
+
+        for note in 0..NUM_VOICES as u8 {
+            manager.note_on(note, 1.0, SAMPLE_RATE, &params);
+        }
+
+        // Release the *newest* voice (note 7). Even though every other voice is older, the
+        // Releasing one should still be picked for stealing.
+        manager.note_off(7, SAMPLE_RATE, &params);
+        manager.note_on(100, 1.0, SAMPLE_RATE, &params);
+
+        assert!(manager
+            .voices
+            .iter()
+            .any(|voice| voice.note == 100 && voice.state == VoiceState::Active));
+        assert!(!manager.voices.iter().any(|voice| voice.note == 7));
+        for note in 0..(NUM_VOICES as u8 - 1) {
+            assert!(manager
+                .voices
+                .iter()
+                .any(|voice| voice.note == note && voice.state == VoiceState::Active));
+        }
+    }
+
+    #[test]
+    fn note_off_only_releases_active_voices_matching_the_note() {
+        let mut manager = manager();
+        let params = ParamicParams::default();
+
+        manager.note_on(60, 1.0, SAMPLE_RATE, &params);
+        manager.note_on(60, 1.0, SAMPLE_RATE, &params);
+        manager.note_on(61, 1.0, SAMPLE_RATE, &params);
+
+        manager.note_off(60, SAMPLE_RATE, &params);
+
+        let released_sixties = manager
+            .voices
+            .iter()
+            .filter(|voice| voice.note == 60 && voice.state == VoiceState::Releasing)
+            .count();
+        assert_eq!(released_sixties, 2);
+        assert!(manager
+            .voices
+            .iter()
+            .any(|voice| voice.note == 61 && voice.state == VoiceState::Active));
+    }
+
+    #[test]
+    fn set_pressure_only_touches_active_voices_on_the_matching_note() {
+        let mut manager = manager();
+        let params = ParamicParams::default();
+
+        manager.note_on(60, 0.4, SAMPLE_RATE, &params);
+        manager.note_on(61, 0.4, SAMPLE_RATE, &params);
+        manager.note_off(61, SAMPLE_RATE, &params);
+
+        manager.set_pressure(60, 0.9);
+        manager.set_pressure(61, 0.9); // Releasing, must be left untouched.
+
+        let voice_60 = manager.voices.iter().find(|voice| voice.note == 60).unwrap();
+        let voice_61 = manager.voices.iter().find(|voice| voice.note == 61).unwrap();
+        assert_eq!(voice_60.velocity, 0.9);
+        assert_eq!(voice_61.velocity, 0.4);
+    }
+}
@@ -0,0 +1,153 @@
+use nih_plug::prelude::Enum;
+
+/// Which synthesis engine a voice's oscillator output is drawn from.
+#[derive(Enum, Debug, PartialEq, Clone, Copy)]
+pub enum SynthEngine {
+    Parametric,
+    Waveguide,
+}
+
+/// A small xorshift PRNG, used to generate the excitation burst without pulling in a `rand`
+/// dependency for a single noise source.
+fn next_noise(state: &mut u32) -> f32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    (*state as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+/// The lowest note frequency the delay line is sized for. Notes below this are clamped up to it,
+/// since going lower would need a longer buffer than the one preallocated in [`WaveguideVoice::new`].
+const MIN_FREQUENCY: f32 = 20.0;
+
+/// A Karplus-Strong-style waveguide voice: a circular delay line whose length sets the pitch, fed
+/// back through a one-pole lowpass reflection filter, with a filtered-noise burst injected on
+/// [`WaveguideVoice::trigger`].
+pub struct WaveguideVoice {
+    sample_rate: f32,
+    /// Preallocated for [`MIN_FREQUENCY`] at the current sample rate; only the first
+    /// `active_length` samples are used as the delay line for the currently playing note, so that
+    /// [`Self::trigger`] never needs to resize it on the audio thread.
+    buffer: Vec<f32>,
+    active_length: usize,
+    read_pos: usize,
+    last_output: f32,
+    rng_state: u32,
+}
+
+impl WaveguideVoice {
+    pub fn new(sample_rate: f32, seed: u32) -> Self {
+        let mut voice = Self {
+            sample_rate,
+            buffer: Vec::new(),
+            active_length: 1,
+            read_pos: 0,
+            last_output: 0.0,
+            rng_state: seed.max(1),
+        };
+        voice.allocate_buffer();
+        voice
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.allocate_buffer();
+    }
+
+    /// (Re)allocates `buffer` to fit [`MIN_FREQUENCY`] at the current sample rate. Only ever
+    /// called from [`Self::new`]/[`Self::set_sample_rate`], which happen off the audio thread, so
+    /// [`Self::trigger`] can stay allocation-free.
+    fn allocate_buffer(&mut self) {
+        let max_length = (self.sample_rate / MIN_FREQUENCY).round().max(2.0) as usize;
+        self.buffer = vec![0.0; max_length];
+        self.active_length = max_length;
+        self.read_pos = 0;
+        self.last_output = 0.0;
+    }
+
+    pub fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.read_pos = 0;
+        self.last_output = 0.0;
+    }
+
+    /// Picks the delay line length for `frequency` out of the preallocated buffer and fills it
+    /// with a burst of noise lowpassed by `brightness` (0 = dark, 1 = bright), ready to be read
+    /// back through [`Self::sample`].
+    pub fn trigger(&mut self, frequency: f32, brightness: f32) {
+        let length = (self.sample_rate / frequency.max(MIN_FREQUENCY))
+            .round()
+            .clamp(2.0, self.buffer.len() as f32) as usize;
+        self.active_length = length;
+        self.read_pos = 0;
+        self.last_output = 0.0;
+
+        let brightness = brightness.clamp(0.0, 1.0);
+        let mut filtered = 0.0;
+        for sample in self.buffer[..length].iter_mut() {
+            let noise = next_noise(&mut self.rng_state);
+            filtered = brightness * noise + (1.0 - brightness) * filtered;
+            *sample = filtered;
+        }
+        for sample in self.buffer[length..].iter_mut() {
+            *sample = 0.0;
+        }
+    }
+
+    /// Advances the delay line by one sample, applying the reflection filter, and returns the
+    /// value read before it.
+    pub fn sample(&mut self, damping: f32) -> f32 {
+        let output = self.buffer[self.read_pos];
+        let reflected = 0.5 * (output + self.last_output) * damping;
+        self.last_output = output;
+        self.buffer[self.read_pos] = reflected;
+        self.read_pos = (self.read_pos + 1) % self.active_length;
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_stays_finite_and_in_bounds_at_high_frequency() {
+        let mut voice = WaveguideVoice::new(44_100.0, 7);
+        voice.trigger(10_000.0, 0.8);
+        assert_eq!(voice.active_length, 4);
+        for _ in 0..256 {
+            assert!(voice.sample(0.999).is_finite());
+        }
+    }
+
+    #[test]
+    fn sample_stays_finite_and_in_bounds_at_the_lowest_supported_frequency() {
+        let mut voice = WaveguideVoice::new(44_100.0, 7);
+        voice.trigger(MIN_FREQUENCY, 0.8);
+        assert_eq!(voice.active_length, voice.buffer.len());
+        for _ in 0..voice.buffer.len() * 2 {
+            assert!(voice.sample(0.999).is_finite());
+        }
+    }
+
+    #[test]
+    fn retriggering_with_a_longer_note_refills_the_newly_used_tail() {
+        let mut voice = WaveguideVoice::new(44_100.0, 7);
+        voice.trigger(10_000.0, 0.8);
+        let short_length = voice.active_length;
+        for _ in 0..short_length * 3 {
+            voice.sample(0.5);
+        }
+
+        voice.trigger(100.0, 0.8);
+        let long_length = voice.active_length;
+        assert!(long_length > short_length);
+
+        // The newly used tail must be freshly filled with this note's noise burst, not left over
+        // from the previous, shorter note or from the zero padding beyond it.
+        assert!(voice.buffer[short_length..long_length].iter().any(|&s| s != 0.0));
+        for _ in 0..long_length * 2 {
+            assert!(voice.sample(0.5).is_finite());
+        }
+    }
+}